@@ -0,0 +1,187 @@
+//! ECS data-flow driving launcher state and rendering.
+//!
+//! `State` (the device/queue/surface/config bundle) lives in the `World` as a
+//! resource; input translated from `WindowEvent`s becomes ECS events, and a
+//! small schedule of systems reacts to them each `RedrawRequested`. Future
+//! launcher UI (buttons, account panels, version lists) should be added as
+//! entities with their own components and a system querying them, rather
+//! than new match arms in `window_event` — nothing has needed that yet, so
+//! no widget components exist in this module until something does.
+
+use std::sync::Arc;
+
+use bevy_ecs::prelude::*;
+use winit::dpi::PhysicalSize;
+use winit::keyboard::KeyCode;
+use winit::window::{Fullscreen, Window};
+
+use crate::State;
+
+/// Emitted from `window_event` when the window is resized; consumed by
+/// [`resize_system`] on the next schedule run.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct ResizeEvent {
+    pub size: PhysicalSize<u32>,
+}
+
+/// Emitted from `window_event` for a non-repeat key press; consumed by
+/// [`keyboard_system`] on the next schedule run.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct KeyboardEvent {
+    pub code: KeyCode,
+}
+
+/// Wraps the winit `Window` so it can live in the `World` as a resource;
+/// inserted once `resumed` has created the window.
+#[derive(Resource, Clone)]
+pub struct WindowHandle(pub Arc<Window>);
+
+/// Whether the launcher is currently borderless-fullscreen; flipped by
+/// [`keyboard_system`]'s handling of `F`.
+#[derive(Resource, Default)]
+pub struct FullscreenState(pub bool);
+
+/// Set by [`keyboard_system`] on `Esc`, and by [`render_system`] when the
+/// surface reports `OutOfMemory`, so `window_event` can ask the event loop
+/// to exit.
+#[derive(Resource, Default)]
+pub struct ExitRequested(pub bool);
+
+/// Set by [`render_system`] after every frame so `window_event` knows to
+/// queue up the next one, keeping the animated background running.
+#[derive(Resource, Default)]
+pub struct RedrawRequested(pub bool);
+
+/// Seconds elapsed since the previous frame, as paced by [`time_system`].
+#[derive(Resource, Default)]
+pub struct DeltaTime(pub f32);
+
+/// Whether this frame made it past frame pacing and should actually update
+/// and render. `time_system` clears this when it skips a frame (the wasm
+/// path, which can't block to sleep off the remainder of the frame budget).
+#[derive(Resource, Default)]
+pub struct ShouldRender(pub bool);
+
+fn time_system(
+    mut state: ResMut<State>,
+    mut dt: ResMut<DeltaTime>,
+    mut should_render: ResMut<ShouldRender>,
+) {
+    match state.tick() {
+        Some(elapsed) => {
+            dt.0 = elapsed;
+            should_render.0 = true;
+        }
+        None => should_render.0 = false,
+    }
+}
+
+fn update_system(mut state: ResMut<State>, dt: Res<DeltaTime>, should_render: Res<ShouldRender>) {
+    if should_render.0 {
+        state.update(dt.0);
+    }
+}
+
+fn resize_system(mut state: ResMut<State>, mut events: EventReader<ResizeEvent>) {
+    for event in events.read() {
+        state.resize(event.size);
+    }
+}
+
+fn toggle_fullscreen(window: &Window, is_fullscreen: &mut bool) {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        *is_fullscreen = !*is_fullscreen;
+        window.set_fullscreen((*is_fullscreen).then_some(Fullscreen::Borderless(None)));
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        use winit::platform::web::WindowExtWebSys;
+
+        let Some(canvas) = window.canvas() else {
+            return;
+        };
+        let next = !*is_fullscreen;
+        if next {
+            let _ = canvas.request_fullscreen();
+        } else if let Some(document) = web_sys::window().and_then(|w| w.document()) {
+            document.exit_fullscreen();
+        }
+        *is_fullscreen = next;
+    }
+}
+
+fn keyboard_system(
+    mut events: EventReader<KeyboardEvent>,
+    window: Option<Res<WindowHandle>>,
+    mut fullscreen: ResMut<FullscreenState>,
+    mut exit_requested: ResMut<ExitRequested>,
+) {
+    for event in events.read() {
+        match event.code {
+            KeyCode::KeyF => {
+                if let Some(window) = &window {
+                    toggle_fullscreen(&window.0, &mut fullscreen.0);
+                }
+            }
+            KeyCode::Escape => exit_requested.0 = true,
+            _ => {}
+        }
+    }
+}
+
+fn render_system(
+    mut state: ResMut<State>,
+    mut exit_requested: ResMut<ExitRequested>,
+    mut redraw_requested: ResMut<RedrawRequested>,
+    should_render: Res<ShouldRender>,
+) {
+    if should_render.0 {
+        match state.render() {
+            Ok(()) => {}
+            Err(wgpu::SurfaceError::OutOfMemory) => {
+                log::error!("surface out of memory, exiting");
+                exit_requested.0 = true;
+            }
+            Err(err) => log::error!("surface error: {err:?}"),
+        }
+    }
+    redraw_requested.0 = true;
+}
+
+/// Builds the per-frame schedule: pacing runs first so `update_system` and
+/// `render_system` see this frame's delta-time and can skip a paced-out
+/// frame; resize reacts to input before the frame is drawn, so a resize and
+/// a redraw delivered in the same batch of events settle on the right
+/// surface size before `render_system` runs. `event_update_system` runs last
+/// to age out `ResizeEvent`s already consumed this frame — a bare `World` /
+/// `Schedule` (unlike `bevy_app::App`) doesn't add that for us, so without it
+/// the event double-buffer would grow forever.
+pub fn build_schedule() -> Schedule {
+    let mut schedule = Schedule::default();
+    schedule.add_systems(
+        (
+            time_system,
+            update_system,
+            resize_system,
+            keyboard_system,
+            render_system,
+            bevy_ecs::event::event_update_system::<ResizeEvent>,
+            bevy_ecs::event::event_update_system::<KeyboardEvent>,
+        )
+            .chain(),
+    );
+    schedule
+}
+
+/// Sets up the resources the schedule above expects to find in the world.
+pub fn init_world(world: &mut World) {
+    world.init_resource::<Events<ResizeEvent>>();
+    world.init_resource::<Events<KeyboardEvent>>();
+    world.init_resource::<ExitRequested>();
+    world.init_resource::<RedrawRequested>();
+    world.init_resource::<DeltaTime>();
+    world.init_resource::<ShouldRender>();
+    world.init_resource::<FullscreenState>();
+}
@@ -1,24 +1,97 @@
+mod ecs;
+
 use std::sync::Arc;
 
 #[cfg(target_arch = "wasm32")]
 use log::Level;
 
+use bevy_ecs::prelude::*;
 use winit::application::ApplicationHandler;
-use winit::event::WindowEvent;
+use winit::event::{ElementState, KeyEvent, WindowEvent};
 use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop, EventLoopProxy};
+use winit::keyboard::{KeyCode, PhysicalKey};
 use winit::window::{Window, WindowId};
 
 use winit::dpi::PhysicalSize;
 
+use wgpu::util::DeviceExt;
+
+use rand::Rng;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{Duration, Instant};
+#[cfg(target_arch = "wasm32")]
+use web_time::{Duration, Instant};
+
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
 
-#[derive(Debug)]
+use ecs::ResizeEvent;
+
+const BACKGROUND_SHADER: &str = include_str!("shaders/background.wgsl");
+const CELLULAR_AUTOMATON_SHADER: &str = include_str!("shaders/cellular_automaton.wgsl");
+
+// Size of the ping-pong textures the cellular-automaton compute shader runs
+// over; kept small since the result is only ever sampled as a blurry-free
+// full-screen background, not a crisp foreground element.
+const GRID_WIDTH: u32 = 256;
+const GRID_HEIGHT: u32 = 256;
+
+// Default cap on how often `State::update`/`render` run; a mostly-static
+// launcher window has no need to spin the GPU faster than this.
+const DEFAULT_TARGET_FPS: f64 = 60.0;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct Vertex {
+    position: [f32; 2],
+    uv: [f32; 2],
+}
+
+impl Vertex {
+    const ATTRIBS: [wgpu::VertexAttribute; 2] =
+        wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2];
+
+    fn layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBS,
+        }
+    }
+}
+
+// A full-screen quad made of two triangles, covering clip space [-1, 1].
+const QUAD_VERTICES: &[Vertex] = &[
+    Vertex { position: [-1.0, -1.0], uv: [0.0, 1.0] },
+    Vertex { position: [1.0, -1.0], uv: [1.0, 1.0] },
+    Vertex { position: [1.0, 1.0], uv: [1.0, 0.0] },
+    Vertex { position: [-1.0, -1.0], uv: [0.0, 1.0] },
+    Vertex { position: [1.0, 1.0], uv: [1.0, 0.0] },
+    Vertex { position: [-1.0, 1.0], uv: [0.0, 0.0] },
+];
+
+#[derive(Resource, Debug)]
 struct State {
     surface: wgpu::Surface<'static>,
     device: wgpu::Device,
     queue: wgpu::Queue,
     config: wgpu::SurfaceConfiguration,
+    render_pipeline: wgpu::RenderPipeline,
+    vertex_buffer: wgpu::Buffer,
+    vertex_count: u32,
+    size: PhysicalSize<u32>,
+    // `None` when the adapter has no compute-shader support (WebGL2, via the
+    // `GL` backend fallback, has none); the background is then left static on
+    // whatever generation-0 seeded, instead of failing device/pipeline
+    // validation by requesting a compute pipeline the backend can't run.
+    compute_pipeline: Option<wgpu::ComputePipeline>,
+    cell_textures: [wgpu::Texture; 2],
+    compute_bind_groups: Option<[wgpu::BindGroup; 2]>,
+    render_bind_groups: [wgpu::BindGroup; 2],
+    generation: u64,
+    last_frame: Instant,
+    target_frame_time: Duration,
 }
 
 impl State {
@@ -36,8 +109,20 @@ impl State {
             })
             .await
             .unwrap();
+        // WebGL2 (the backend behind the `GL` fallback above) doesn't support
+        // the full default limits, so every wasm32 build, whichever browser
+        // backend it ends up on, requests WebGL2-level limits (with the
+        // adapter's texture resolution); native builds keep wgpu's defaults.
+        let limits = if cfg!(target_arch = "wasm32") {
+            wgpu::Limits::downlevel_webgl2_defaults().using_resolution(adapter.limits())
+        } else {
+            wgpu::Limits::default()
+        };
         let (device, queue) = adapter
-            .request_device(&wgpu::DeviceDescriptor::default())
+            .request_device(&wgpu::DeviceDescriptor {
+                required_limits: limits,
+                ..Default::default()
+            })
             .await
             .unwrap();
         let caps = surface.get_capabilities(&adapter);
@@ -60,32 +145,379 @@ assert!(size.width > 0 && size.height > 0, "inner size must not be 0 0 during wg
             view_formats: vec![],
         };
         surface.configure(&device, &config);
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("background shader"),
+            source: wgpu::ShaderSource::Wgsl(BACKGROUND_SHADER.into()),
+        });
+
+        let render_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("background render bind group layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                ],
+            });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("render pipeline layout"),
+            bind_group_layouts: &[&render_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("background render pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[Vertex::layout()],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: config.format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("quad vertex buffer"),
+            contents: bytemuck::cast_slice(QUAD_VERTICES),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        let background_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("background sampler"),
+            mag_filter: wgpu::FilterMode::Nearest,
+            min_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        // WebGL2 (the `GL` backend fallback) has no compute stage at all, so
+        // only build the compute side of the automaton when the adapter
+        // actually supports it; otherwise the background stays on whatever
+        // generation 0 seeds below.
+        let supports_compute = adapter
+            .get_downlevel_capabilities()
+            .flags
+            .contains(wgpu::DownlevelFlags::COMPUTE_SHADERS);
+
+        let compute_pipeline_and_layout = supports_compute.then(|| {
+            let compute_bind_group_layout =
+                device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("cellular automaton compute bind group layout"),
+                    entries: &[
+                        wgpu::BindGroupLayoutEntry {
+                            binding: 0,
+                            visibility: wgpu::ShaderStages::COMPUTE,
+                            ty: wgpu::BindingType::StorageTexture {
+                                access: wgpu::StorageTextureAccess::ReadOnly,
+                                format: wgpu::TextureFormat::Rgba8Unorm,
+                                view_dimension: wgpu::TextureViewDimension::D2,
+                            },
+                            count: None,
+                        },
+                        wgpu::BindGroupLayoutEntry {
+                            binding: 1,
+                            visibility: wgpu::ShaderStages::COMPUTE,
+                            ty: wgpu::BindingType::StorageTexture {
+                                access: wgpu::StorageTextureAccess::WriteOnly,
+                                format: wgpu::TextureFormat::Rgba8Unorm,
+                                view_dimension: wgpu::TextureViewDimension::D2,
+                            },
+                            count: None,
+                        },
+                    ],
+                });
+
+            let compute_pipeline_layout =
+                device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                    label: Some("cellular automaton compute pipeline layout"),
+                    bind_group_layouts: &[&compute_bind_group_layout],
+                    push_constant_ranges: &[],
+                });
+
+            let compute_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("cellular automaton compute shader"),
+                source: wgpu::ShaderSource::Wgsl(CELLULAR_AUTOMATON_SHADER.into()),
+            });
+
+            let compute_pipeline =
+                device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                    label: Some("cellular automaton compute pipeline"),
+                    layout: Some(&compute_pipeline_layout),
+                    module: &compute_shader,
+                    entry_point: Some("main"),
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                    cache: None,
+                });
+
+            (compute_pipeline, compute_bind_group_layout)
+        });
+
+        if !supports_compute {
+            log::warn!(
+                "adapter has no compute-shader support; launcher background will be static"
+            );
+        }
+
+        let cell_texture_size = wgpu::Extent3d {
+            width: GRID_WIDTH,
+            height: GRID_HEIGHT,
+            depth_or_array_layers: 1,
+        };
+        let cell_texture_descriptor = wgpu::TextureDescriptor {
+            label: Some("cellular automaton texture"),
+            size: cell_texture_size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::STORAGE_BINDING
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        };
+        let cell_textures = [
+            device.create_texture(&cell_texture_descriptor),
+            device.create_texture(&cell_texture_descriptor),
+        ];
+        let cell_texture_views = [
+            cell_textures[0].create_view(&wgpu::TextureViewDescriptor::default()),
+            cell_textures[1].create_view(&wgpu::TextureViewDescriptor::default()),
+        ];
+
+        // Seed generation 0 with random live/dead cells on the CPU, then
+        // upload it; the compute shader takes over from there.
+        let mut rng = rand::rng();
+        let mut seed = vec![0u8; (GRID_WIDTH * GRID_HEIGHT * 4) as usize];
+        for cell in seed.chunks_exact_mut(4) {
+            let value = if rng.random_bool(0.5) { 255 } else { 0 };
+            cell.copy_from_slice(&[value, value, value, 255]);
+        }
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &cell_textures[0],
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            &seed,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(GRID_WIDTH * 4),
+                rows_per_image: Some(GRID_HEIGHT),
+            },
+            cell_texture_size,
+        );
+
+        let (compute_pipeline, compute_bind_groups) =
+            if let Some((compute_pipeline, compute_bind_group_layout)) = compute_pipeline_and_layout
+            {
+                let bind_groups = [
+                    device.create_bind_group(&wgpu::BindGroupDescriptor {
+                        label: Some("cellular automaton compute bind group (a -> b)"),
+                        layout: &compute_bind_group_layout,
+                        entries: &[
+                            wgpu::BindGroupEntry {
+                                binding: 0,
+                                resource: wgpu::BindingResource::TextureView(
+                                    &cell_texture_views[0],
+                                ),
+                            },
+                            wgpu::BindGroupEntry {
+                                binding: 1,
+                                resource: wgpu::BindingResource::TextureView(
+                                    &cell_texture_views[1],
+                                ),
+                            },
+                        ],
+                    }),
+                    device.create_bind_group(&wgpu::BindGroupDescriptor {
+                        label: Some("cellular automaton compute bind group (b -> a)"),
+                        layout: &compute_bind_group_layout,
+                        entries: &[
+                            wgpu::BindGroupEntry {
+                                binding: 0,
+                                resource: wgpu::BindingResource::TextureView(
+                                    &cell_texture_views[1],
+                                ),
+                            },
+                            wgpu::BindGroupEntry {
+                                binding: 1,
+                                resource: wgpu::BindingResource::TextureView(
+                                    &cell_texture_views[0],
+                                ),
+                            },
+                        ],
+                    }),
+                ];
+                (Some(compute_pipeline), Some(bind_groups))
+            } else {
+                (None, None)
+            };
+
+        let render_bind_groups = [
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("background render bind group (texture a)"),
+                layout: &render_bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&cell_texture_views[0]),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&background_sampler),
+                    },
+                ],
+            }),
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("background render bind group (texture b)"),
+                layout: &render_bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&cell_texture_views[1]),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&background_sampler),
+                    },
+                ],
+            }),
+        ];
+
         Self {
             surface,
             device,
             queue,
             config,
+            render_pipeline,
+            vertex_buffer,
+            vertex_count: QUAD_VERTICES.len() as u32,
+            size,
+            compute_pipeline,
+            cell_textures,
+            compute_bind_groups,
+            render_bind_groups,
+            generation: 0,
+            last_frame: Instant::now(),
+            target_frame_time: Duration::from_secs_f64(1.0 / DEFAULT_TARGET_FPS),
         }
     }
 }
 
 impl State {
+    pub fn set_target_fps(&mut self, fps: f64) {
+        self.target_frame_time = Duration::from_secs_f64(1.0 / fps);
+    }
+
+    /// Paces the frame loop to `target_frame_time` and returns the elapsed
+    /// time since the previous call, in seconds. On native, blocks for the
+    /// remainder of the frame budget; on wasm, where blocking the event loop
+    /// isn't an option, returns `None` to tell the caller to skip this frame
+    /// instead.
+    pub fn tick(&mut self) -> Option<f32> {
+        let elapsed = self.last_frame.elapsed();
+        if elapsed < self.target_frame_time {
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                std::thread::sleep(self.target_frame_time - elapsed);
+            }
+            #[cfg(target_arch = "wasm32")]
+            {
+                return None;
+            }
+        }
+        let now = Instant::now();
+        let dt = (now - self.last_frame).as_secs_f32();
+        self.last_frame = now;
+        Some(dt)
+    }
+
+    pub fn update(&mut self, _dt: f32) {
+        // No animated CPU-side state yet; the cellular-automaton background
+        // advances on the GPU independently of dt. This is the hook future
+        // launcher UI (buttons, transitions, ...) will tick from.
+    }
+
     pub fn resize(&mut self, size: winit::dpi::PhysicalSize<u32>) {
+        if size.width == 0 || size.height == 0 {
+            // A minimized window reports a 0x0 size; configuring the surface
+            // with that would panic, so just remember it and skip rendering
+            // until the window is restored.
+            self.size = size;
+            return;
+        }
+        self.size = size;
         self.config.width = size.width;
         self.config.height = size.height;
         self.surface.configure(&self.device, &self.config);
     }
 
-    pub fn render(&self) {
-        let frame = self.surface.get_current_texture().unwrap();
+    pub fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        if self.size.width == 0 || self.size.height == 0 {
+            return Ok(());
+        }
+
+        let frame = match self.surface.get_current_texture() {
+            Ok(frame) => frame,
+            Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                self.surface.configure(&self.device, &self.config);
+                return Ok(());
+            }
+            Err(wgpu::SurfaceError::Timeout) => return Ok(()),
+            Err(err) => return Err(err),
+        };
         let view = frame
             .texture
             .create_view(&wgpu::TextureViewDescriptor::default());
         let mut encoder = self
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+
+        let read_index = (self.generation % 2) as usize;
+        let mut write_index = read_index;
+        if let (Some(compute_pipeline), Some(compute_bind_groups)) =
+            (&self.compute_pipeline, &self.compute_bind_groups)
         {
-            let _render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            let mut compute_pass =
+                encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+            compute_pass.set_pipeline(compute_pipeline);
+            compute_pass.set_bind_group(0, &compute_bind_groups[read_index], &[]);
+            compute_pass.dispatch_workgroups(GRID_WIDTH / 8, GRID_HEIGHT / 8, 1);
+            write_index = 1 - read_index;
+        }
+
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
                     view: &view,
                     resolve_target: None,
@@ -96,20 +528,48 @@ impl State {
                 })],
                 ..Default::default()
             });
+            render_pass.set_pipeline(&self.render_pipeline);
+            render_pass.set_bind_group(0, &self.render_bind_groups[write_index], &[]);
+            render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+            render_pass.draw(0..self.vertex_count, 0..1);
         }
         self.queue.submit(std::iter::once(encoder.finish()));
         frame.present();
+        if self.compute_pipeline.is_some() {
+            self.generation = self.generation.wrapping_add(1);
+        }
+        Ok(())
     }
 }
 
-#[derive(Default)]
 struct App {
     window: Option<Arc<Window>>,
-    state: Option<State>,
+    world: World,
+    schedule: Schedule,
     #[cfg(target_arch = "wasm32")]
     event_loop_proxy: Option<EventLoopProxy<State>>,
 }
 
+impl Default for App {
+    fn default() -> Self {
+        let mut world = World::new();
+        ecs::init_world(&mut world);
+        Self {
+            window: None,
+            world,
+            schedule: ecs::build_schedule(),
+            #[cfg(target_arch = "wasm32")]
+            event_loop_proxy: None,
+        }
+    }
+}
+
+impl App {
+    fn state_ready(&self) -> bool {
+        self.world.get_resource::<State>().is_some()
+    }
+}
+
 impl ApplicationHandler<State> for App {
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
         let mut attributes = Window::default_attributes()
@@ -119,7 +579,7 @@ impl ApplicationHandler<State> for App {
         #[cfg(target_arch = "wasm32")]
         {
             use winit::platform::web::WindowAttributesExtWebSys;
-            
+
             let canvas = web_sys::window()
                 .and_then(|win| win.document())
                 .and_then(|doc| doc.get_element_by_id("canvas"))
@@ -129,6 +589,8 @@ impl ApplicationHandler<State> for App {
 
         let window = Arc::new(event_loop.create_window(attributes).unwrap());
         self.window = Some(window.clone());
+        self.world
+            .insert_resource(ecs::WindowHandle(window.clone()));
 
         cfg_if::cfg_if! {
             if #[cfg(target_arch = "wasm32")] {
@@ -138,7 +600,7 @@ impl ApplicationHandler<State> for App {
                     event_loop_proxy.send_event(state);
                 });
             } else {
-                self.state = Some(pollster::block_on(State::new(window)));
+                self.world.insert_resource(pollster::block_on(State::new(window)));
             }
         }
     }
@@ -147,23 +609,46 @@ impl ApplicationHandler<State> for App {
         match event {
             WindowEvent::CloseRequested => event_loop.exit(),
             WindowEvent::Resized(size) => {
-                if let Some(state) = &mut self.state {
-                    state.resize(size);
+                self.world.send_event(ResizeEvent { size });
+                if let Some(window) = &self.window {
+                    window.request_redraw();
                 }
-            },
+            }
             WindowEvent::RedrawRequested => {
-                if let Some(state) = &self.state {
-                    state.render();
+                if self.state_ready() {
+                    self.schedule.run(&mut self.world);
+                    if self.world.resource::<ecs::ExitRequested>().0 {
+                        event_loop.exit();
+                    } else if self.world.resource::<ecs::RedrawRequested>().0 {
+                        if let Some(window) = &self.window {
+                            window.request_redraw();
+                        }
+                    }
+                }
+            }
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        physical_key: PhysicalKey::Code(code),
+                        state: ElementState::Pressed,
+                        repeat: false,
+                        ..
+                    },
+                ..
+            } => {
+                self.world.send_event(ecs::KeyboardEvent { code });
+                if let Some(window) = &self.window {
+                    window.request_redraw();
                 }
             }
             _ => (),
         }
     }
-    
+
     fn user_event(&mut self, _event_loop: &ActiveEventLoop, event: State) {
         #[cfg(target_arch = "wasm32")]
         {
-            self.state = Some(event);
+            self.world.insert_resource(event);
             if let Some(window) = &self.window {
                 window.request_redraw();
             }
@@ -184,7 +669,8 @@ pub fn run() {
 
     // desktop don't need user event
     let event_loop = EventLoop::<State>::with_user_event().build().unwrap();
-    event_loop.set_control_flow(ControlFlow::Wait);
+    // The animated compute-shader background needs to redraw continuously.
+    event_loop.set_control_flow(ControlFlow::Poll);
     let mut app = App::default();
     #[cfg(target_arch = "wasm32")]
     {